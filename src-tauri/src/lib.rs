@@ -1,14 +1,57 @@
 use colored::*;
+use exif;
+use image::codecs::jpeg::JpegEncoder;
 use image::imageops::FilterType;
-use image::GenericImageView;
+use image::{DynamicImage, GenericImageView};
 use infer;
-use serde::Deserialize;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
+use std::io::BufReader;
 use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::Instant;
 use tauri::async_runtime::spawn; // Import the spawn function for async tasks
+use tauri::{AppHandle, Emitter};
+use uuid::Uuid;
+
+const PROGRESS_EVENT: &str = "process-images://progress";
+
+/// Extensions `process_images`/`process_images_backgrounded` will queue for resizing.
+///
+/// `heic`/`heif`/`jxl` are deliberately absent: the `image` crate has no decoder for any of
+/// them (HEIF is omitted upstream for HEVC licensing reasons, and JPEG XL has no decoder either,
+/// same as the encoder gap noted in `resolve_output_format`), so accepting them as input would
+/// just fail at `image::open` instead of resizing anything.
+const VALID_EXTENSIONS: &[&str] = &["jpg", "jpeg", "png", "gif", "webp", "avif"];
+
+/// MIME types `resize_image` accepts once `infer` has sniffed the actual file contents.
+const VALID_MIME_TYPES: &[&str] = &[
+    "image/png",
+    "image/jpeg",
+    "image/gif",
+    "image/webp",
+    "image/avif",
+];
+
+/// In-flight and finished backgrounded jobs, keyed by job id. Entries are never evicted,
+/// so `job_status` stays answerable for the lifetime of the app.
+static JOBS: Lazy<Mutex<HashMap<String, Arc<JobState>>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+struct JobState {
+    cancelled: AtomicBool,
+    done: AtomicUsize,
+    /// Output count, filled in once `build_file_plans` has run in the background (see `planned`);
+    /// starts at 0 because planning a huge folder is real I/O we don't want blocking job creation.
+    total: AtomicUsize,
+    /// Set once `total` holds a real count, so `job_status` can tell "still planning" (0 done,
+    /// 0 total) apart from "genuinely empty input folder, already finished" (also 0 and 0).
+    planned: AtomicBool,
+    results: Mutex<Vec<serde_json::Value>>,
+}
 
 #[derive(Debug, Deserialize, Clone)]
 struct ResizeOptions {
@@ -18,19 +61,490 @@ struct ResizeOptions {
     height: Option<u32>,
     keep_aspect_ratio: bool,
     overwrite: bool,
+    /// Target format to transcode into (e.g. "webp", "avif"). Leave unset to keep the input's format.
+    output_format: Option<String>,
+    /// Strip EXIF/ICC/GPS metadata on save. Defaults to true (stripped) for privacy; set false to
+    /// preserve the source color profile.
+    #[serde(default = "default_strip_metadata")]
+    strip_metadata: bool,
+    /// Pre-resize rules; a file failing any rule is skipped with status "filtered" instead of resized.
+    filters: Option<DimensionFilters>,
+    /// Size variants to produce per input. When empty, a single output uses width/height/keep_aspect_ratio directly.
+    #[serde(default)]
+    presets: Vec<Preset>,
+    /// Filename template for preset outputs, e.g. "{stem}_{preset}.{ext}". Supports {stem}, {preset}, {w}, {h}, {ext}.
+    output_template: Option<String>,
+}
+
+fn default_strip_metadata() -> bool {
+    true
+}
+
+#[derive(Debug, Deserialize, Clone)]
+struct Preset {
+    name: String,
+    width: Option<u32>,
+    height: Option<u32>,
+    keep_aspect_ratio: Option<bool>,
+}
+
+const DEFAULT_OUTPUT_TEMPLATE: &str = "{stem}_{preset}.{ext}";
+
+/// Applies a preset's overrides on top of the base options, falling back to the base value
+/// wherever the preset leaves a field unset.
+fn options_for_preset(options: &ResizeOptions, preset: &Preset) -> ResizeOptions {
+    let mut preset_options = options.clone();
+    if preset.width.is_some() {
+        preset_options.width = preset.width;
+    }
+    if preset.height.is_some() {
+        preset_options.height = preset.height;
+    }
+    if let Some(keep_aspect_ratio) = preset.keep_aspect_ratio {
+        preset_options.keep_aspect_ratio = keep_aspect_ratio;
+    }
+    preset_options
+}
+
+/// Base filename stem for an output path. When a format conversion is requested, the original
+/// extension is folded into the stem (`photo.jpg` -> `photo.jpg`) so two source files that only
+/// differ by extension (e.g. `photo.jpg` and `photo.png`) don't collide on the same converted
+/// output name.
+fn disambiguated_stem(input_path: &Path, output_format: &Option<String>) -> String {
+    let stem = input_path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("output");
+
+    match output_format {
+        Some(_) => {
+            let orig_ext = input_path
+                .extension()
+                .and_then(|e| e.to_str())
+                .unwrap_or("");
+            if orig_ext.is_empty() {
+                stem.to_string()
+            } else {
+                format!("{}.{}", stem, orig_ext)
+            }
+        }
+        None => stem.to_string(),
+    }
+}
+
+/// Resolves `{stem}`, `{preset}`, `{w}`, `{h}`, `{ext}` in `template` against one preset's output.
+fn render_output_path(
+    output_folder: &Path,
+    input_path: &Path,
+    template: &str,
+    preset_name: &str,
+    width: u32,
+    height: u32,
+    output_format: &Option<String>,
+) -> PathBuf {
+    let stem = disambiguated_stem(input_path, output_format);
+    let ext = output_format.clone().unwrap_or_else(|| {
+        input_path
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("")
+            .to_string()
+    });
+
+    let filename = template
+        .replace("{stem}", &stem)
+        .replace("{preset}", preset_name)
+        .replace("{w}", &width.to_string())
+        .replace("{h}", &height.to_string())
+        .replace("{ext}", &ext);
+
+    output_folder.join(filename)
+}
+
+/// A single resize to run: the (possibly preset-overridden) options to resize with and the
+/// resolved output path, tagged with the preset name it came from (if any).
+struct ResizeTask {
+    preset: Option<String>,
+    options: ResizeOptions,
+    output_path: PathBuf,
+}
+
+/// What to do with one directory entry, decided up front so `process_images`/
+/// `process_images_backgrounded` can size their progress counter before spawning anything.
+enum FilePlan {
+    Tasks(Vec<ResizeTask>),
+    Filtered(String),
+    Unsupported,
+}
+
+/// Expands one input file into the resize task(s) it should produce: a single task mirroring
+/// the pre-preset behavior when `presets` is empty, or one task per preset otherwise. Preset
+/// output filenames need the *target* width/height, so this does a cheap dimension probe (no
+/// full decode) per preset; a probe failure just falls back to 0x0 in the filename; the file's
+/// own `resize_image` call still fails properly and reports a real error for that task.
+fn build_tasks(path: &Path, options: &ResizeOptions) -> Vec<ResizeTask> {
+    if options.presets.is_empty() {
+        return vec![ResizeTask {
+            preset: None,
+            output_path: build_output_path(&options.output_folder, path, &options.output_format),
+            options: options.clone(),
+        }];
+    }
+
+    let template = options
+        .output_template
+        .as_deref()
+        .unwrap_or(DEFAULT_OUTPUT_TEMPLATE);
+
+    options
+        .presets
+        .iter()
+        .map(|preset| {
+            let preset_options = options_for_preset(options, preset);
+            let (width, height) = imagesize::size(path)
+                .ok()
+                .and_then(|dims| {
+                    // `resize_image` sizes against the EXIF-corrected image, and the quarter-turn
+                    // tags (5-8) swap the displayed width/height, so swap the probed ones to match.
+                    let (orig_width, orig_height) = match read_exif_orientation(path) {
+                        Some(5..=8) => (dims.height as u32, dims.width as u32),
+                        _ => (dims.width as u32, dims.height as u32),
+                    };
+                    resolve_target_dimensions(
+                        orig_width,
+                        orig_height,
+                        preset_options.width,
+                        preset_options.height,
+                        preset_options.keep_aspect_ratio,
+                    )
+                    .ok()
+                })
+                .unwrap_or((0, 0));
+
+            let output_path = render_output_path(
+                &options.output_folder,
+                path,
+                template,
+                &preset.name,
+                width,
+                height,
+                &preset_options.output_format,
+            );
+
+            ResizeTask {
+                preset: Some(preset.name.clone()),
+                options: preset_options,
+                output_path,
+            }
+        })
+        .collect()
+}
+
+const MANIFEST_FILE_NAME: &str = ".swyfts-manifest.json";
+
+/// One output's last-known content+params hash, keyed by its path in the manifest.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct ManifestEntry {
+    hash: String,
+}
+
+type Manifest = HashMap<String, ManifestEntry>;
+
+fn manifest_path(output_folder: &Path) -> PathBuf {
+    output_folder.join(MANIFEST_FILE_NAME)
+}
+
+/// Loads the sidecar manifest for `output_folder`, or an empty one if it doesn't exist yet
+/// (first run, or the folder predates this feature).
+fn load_manifest(output_folder: &Path) -> Manifest {
+    fs::read_to_string(manifest_path(output_folder))
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+fn save_manifest(output_folder: &Path, manifest: &Manifest) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(manifest)
+        .map_err(|e| format!("Error serializing manifest: {}", e))?;
+    fs::write(manifest_path(output_folder), json).map_err(|e| {
+        format!(
+            "Error writing manifest {}: {}",
+            manifest_path(output_folder).display(),
+            e
+        )
+    })
+}
+
+/// Hashes an input's content together with the resize parameters that determine its output, so
+/// the manifest can tell "source changed" apart from "same source, different settings".
+fn compute_task_hash(input_path: &Path, options: &ResizeOptions) -> Result<String, String> {
+    let bytes = fs::read(input_path)
+        .map_err(|e| format!("Error reading {}: {}", input_path.display(), e))?;
+    let content_digest = md5::compute(&bytes);
+
+    let params = format!(
+        "{:?}|{:?}|{}|{:?}|{}",
+        options.width,
+        options.height,
+        options.keep_aspect_ratio,
+        options.output_format,
+        options.strip_metadata
+    );
+    let params_digest = md5::compute(params.as_bytes());
+
+    Ok(format!("{:x}-{:x}", content_digest, params_digest))
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+struct DimensionFilters {
+    min_width: Option<u32>,
+    max_width: Option<u32>,
+    min_height: Option<u32>,
+    max_height: Option<u32>,
+    min_bytes: Option<u64>,
+    max_bytes: Option<u64>,
+    include_extensions: Option<Vec<String>>,
+    exclude_extensions: Option<Vec<String>>,
+}
+
+/// Checks `path` (with lowercased extension `ext`) against `filters`, probing file size and
+/// dimensions cheaply (no full decode) only when a rule actually needs them.
+///
+/// Returns `Err` naming the first rule that failed, so callers can surface it as the result message.
+fn evaluate_filters(path: &Path, ext: &str, filters: &DimensionFilters) -> Result<(), String> {
+    if let Some(include) = &filters.include_extensions {
+        if !include
+            .iter()
+            .any(|allowed| allowed.eq_ignore_ascii_case(ext))
+        {
+            return Err(format!(
+                "extension \"{}\" is not in include_extensions",
+                ext
+            ));
+        }
+    }
+    if let Some(exclude) = &filters.exclude_extensions {
+        if exclude
+            .iter()
+            .any(|blocked| blocked.eq_ignore_ascii_case(ext))
+        {
+            return Err(format!("extension \"{}\" is in exclude_extensions", ext));
+        }
+    }
+
+    if filters.min_bytes.is_some() || filters.max_bytes.is_some() {
+        let size = fs::metadata(path)
+            .map_err(|e| format!("Could not read file size: {}", e))?
+            .len();
+
+        if let Some(min_bytes) = filters.min_bytes {
+            if size < min_bytes {
+                return Err(format!(
+                    "file size {} bytes is below min_bytes ({})",
+                    size, min_bytes
+                ));
+            }
+        }
+        if let Some(max_bytes) = filters.max_bytes {
+            if size > max_bytes {
+                return Err(format!(
+                    "file size {} bytes is above max_bytes ({})",
+                    size, max_bytes
+                ));
+            }
+        }
+    }
+
+    if filters.min_width.is_some()
+        || filters.max_width.is_some()
+        || filters.min_height.is_some()
+        || filters.max_height.is_some()
+    {
+        let dims =
+            imagesize::size(path).map_err(|e| format!("Could not probe dimensions: {}", e))?;
+        let (width, height) = (dims.width as u32, dims.height as u32);
+
+        if let Some(min_width) = filters.min_width {
+            if width < min_width {
+                return Err(format!(
+                    "width {} is below min_width ({})",
+                    width, min_width
+                ));
+            }
+        }
+        if let Some(max_width) = filters.max_width {
+            if width > max_width {
+                return Err(format!(
+                    "width {} is above max_width ({})",
+                    width, max_width
+                ));
+            }
+        }
+        if let Some(min_height) = filters.min_height {
+            if height < min_height {
+                return Err(format!(
+                    "height {} is below min_height ({})",
+                    height, min_height
+                ));
+            }
+        }
+        if let Some(max_height) = filters.max_height {
+            if height > max_height {
+                return Err(format!(
+                    "height {} is above max_height ({})",
+                    height, max_height
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// What `resize_image` actually did, reported back in each file's result entry.
+struct ResizeReport {
+    orientation_corrected: bool,
+    metadata_stripped: bool,
+}
+
+/// Resolves an `output_format` string to the `image` crate format to encode with, if any.
+///
+/// Returns `Ok(None)` when no conversion was requested, in which case the caller should let
+/// `save` infer the encoder from `output_path`'s extension, same as before this option existed.
+fn resolve_output_format(
+    output_format: &Option<String>,
+) -> Result<Option<image::ImageFormat>, String> {
+    let Some(fmt) = output_format else {
+        return Ok(None);
+    };
+
+    let normalized = fmt.to_lowercase();
+    if normalized == "jxl" {
+        return Err(
+            "JPEG XL output is not supported yet: the image crate has no JXL encoder.".to_string(),
+        );
+    }
+
+    image::ImageFormat::from_extension(&normalized)
+        .map(Some)
+        .ok_or_else(|| format!("Unknown output_format: {}", fmt))
+}
+
+/// Builds the destination path for `input_path`, swapping its extension for `output_format`
+/// when a conversion was requested, or keeping the input's own extension otherwise. See
+/// `disambiguated_stem` for why a conversion folds the original extension into the stem.
+fn build_output_path(
+    output_folder: &Path,
+    input_path: &Path,
+    output_format: &Option<String>,
+) -> PathBuf {
+    match output_format {
+        Some(fmt) => output_folder.join(format!(
+            "{}.{}",
+            disambiguated_stem(input_path, output_format),
+            fmt.to_lowercase()
+        )),
+        None => output_folder.join(input_path.file_name().unwrap()),
+    }
+}
+
+/// Reads the EXIF orientation tag (1-8) from `input_path`, if the file carries one.
+fn read_exif_orientation(input_path: &Path) -> Option<u32> {
+    let file = fs::File::open(input_path).ok()?;
+    let mut reader = BufReader::new(file);
+    let exif = exif::Reader::new().read_from_container(&mut reader).ok()?;
+    exif.get_field(exif::Tag::Orientation, exif::In::PRIMARY)?
+        .value
+        .get_uint(0)
+}
+
+/// Applies the rotation/flip implied by an EXIF orientation tag so the image displays upright.
+fn apply_orientation(img: DynamicImage, orientation: u32) -> DynamicImage {
+    match orientation {
+        2 => img.fliph(),
+        3 => img.rotate180(),
+        4 => img.flipv(),
+        5 => img.rotate90().fliph(),
+        6 => img.rotate90(),
+        7 => img.rotate270().fliph(),
+        8 => img.rotate270(),
+        _ => img,
+    }
+}
+
+/// Reads the source ICC color profile, if any, so it can be carried over when metadata isn't stripped.
+fn read_icc_profile(input_path: &Path) -> Option<Vec<u8>> {
+    let reader = image::ImageReader::open(input_path)
+        .ok()?
+        .with_guessed_format()
+        .ok()?;
+    let mut decoder = reader.into_decoder().ok()?;
+    decoder.icc_profile().ok().flatten()
+}
+
+/// Saves `resized_img`, embedding `icc_profile` when the target format supports it (currently JPEG only).
+/// Returns whether the output actually ended up stripped of metadata.
+fn save_image(
+    resized_img: &DynamicImage,
+    output_path: &Path,
+    format: image::ImageFormat,
+    icc_profile: Option<Vec<u8>>,
+) -> Result<bool, String> {
+    if let (Some(icc), image::ImageFormat::Jpeg) = (&icc_profile, format) {
+        let mut out = fs::File::create(output_path)
+            .map_err(|e| format!("Error creating {}: {}", output_path.display(), e))?;
+        let mut encoder = JpegEncoder::new(&mut out);
+        encoder
+            .set_icc_profile(icc.clone())
+            .map_err(|e| format!("Error attaching ICC profile: {}", e))?;
+        encoder
+            .encode_image(resized_img)
+            .map_err(|e| format!("Error saving image: {}", e))?;
+        return Ok(false);
+    }
+
+    resized_img
+        .save_with_format(output_path, format)
+        .map_err(|e| format!("Error saving image: {}", e))?;
+    Ok(true)
+}
+
+/// Computes the target size for a resize, either preserving the source aspect ratio off a
+/// single requested dimension, or using both `width`/`height` (falling back to the original
+/// value for whichever one is unset) when the aspect ratio doesn't need to be preserved.
+fn resolve_target_dimensions(
+    orig_width: u32,
+    orig_height: u32,
+    width: Option<u32>,
+    height: Option<u32>,
+    keep_aspect_ratio: bool,
+) -> Result<(u32, u32), String> {
+    if keep_aspect_ratio {
+        if let Some(width) = width {
+            let height = (width as f64 / orig_width as f64 * orig_height as f64).round() as u32;
+            Ok((width, height))
+        } else if let Some(height) = height {
+            let width = (height as f64 / orig_height as f64 * orig_width as f64).round() as u32;
+            Ok((width, height))
+        } else {
+            Err("Width or height required when preserving aspect ratio.".to_string())
+        }
+    } else {
+        Ok((width.unwrap_or(orig_width), height.unwrap_or(orig_height)))
+    }
 }
 
 fn resize_image(
     input_path: &Path,
     output_path: &Path,
     options: &ResizeOptions,
-) -> Result<(), String> {
+) -> Result<ResizeReport, String> {
     // Check if the file format is valid before proceeding
     let file_type = infer::get_from_path(input_path)
         .map_err(|e| format!("Error reading file: {}", e))?
         .ok_or_else(|| format!("Could not determine file type for {}", input_path.display()))?;
 
-    if !["image/png", "image/jpeg", "image/gif", "image/webp"].contains(&file_type.mime_type()) {
+    if !VALID_MIME_TYPES.contains(&file_type.mime_type()) {
         return Err(format!(
             "Unsupported format: {} (detected as {})",
             input_path.display(),
@@ -38,31 +552,42 @@ fn resize_image(
         ));
     }
 
+    let orientation = read_exif_orientation(input_path);
+    let icc_profile = if options.strip_metadata {
+        None
+    } else {
+        read_icc_profile(input_path)
+    };
+
     let img = image::open(input_path).map_err(|e| format!("Error opening image: {}", e))?;
-    let (orig_width, orig_height) = img.dimensions();
 
-    let (width, height) = if options.keep_aspect_ratio {
-        if let Some(width) = options.width {
-            let height = (width as f64 / orig_width as f64 * orig_height as f64).round() as u32;
-            (width, height)
-        } else if let Some(height) = options.height {
-            let width = (height as f64 / orig_height as f64 * orig_width as f64).round() as u32;
-            (width, height)
-        } else {
-            return Err("Width or height required when preserving aspect ratio.".to_string());
-        }
-    } else {
-        (
-            options.width.unwrap_or(orig_width),
-            options.height.unwrap_or(orig_height),
-        )
+    // Correct orientation before computing the target size: for the quarter-turn tags (5-8) the
+    // displayed width/height are swapped from the raw decode, so resizing against the raw
+    // dimensions would size against the wrong aspect ratio.
+    let orientation_corrected = matches!(orientation, Some(o) if o != 1);
+    let img = match orientation {
+        Some(o) if o != 1 => apply_orientation(img, o),
+        _ => img,
     };
+    let (orig_width, orig_height) = img.dimensions();
+
+    let (width, height) = resolve_target_dimensions(
+        orig_width,
+        orig_height,
+        options.width,
+        options.height,
+        options.keep_aspect_ratio,
+    )?;
 
     let resized_img = img.resize(width, height, FilterType::Lanczos3);
 
-    resized_img
-        .save(output_path)
-        .map_err(|e| format!("Error saving image: {}", e))?;
+    let format = match resolve_output_format(&options.output_format)? {
+        Some(format) => format,
+        None => image::ImageFormat::from_path(output_path)
+            .map_err(|e| format!("Error determining output format: {}", e))?,
+    };
+
+    let metadata_stripped = save_image(&resized_img, output_path, format, icc_profile)?;
 
     println!(
         "{} [{}] {} {} {}",
@@ -73,94 +598,286 @@ fn resize_image(
         output_path.display().to_string().magenta(),
     );
 
-    Ok(())
+    Ok(ResizeReport {
+        orientation_corrected,
+        metadata_stripped,
+    })
 }
 
-#[tauri::command]
-async fn process_images(options: ResizeOptions) -> Result<String, String> {
-    if !options.input_folder.exists() {
-        return Err(format!(
-            "Input folder does not exist: {:?}",
-            options.input_folder
-        ));
+/// Emits a `PROGRESS_EVENT` carrying the just-finished file's result plus a running `{done, total}` counter.
+fn emit_progress(app: &AppHandle, result: &serde_json::Value, done: &AtomicUsize, total: usize) {
+    let done = done.fetch_add(1, Ordering::SeqCst) + 1;
+    let payload = serde_json::json!({
+        "result": result,
+        "done": done,
+        "total": total,
+    });
+    if let Err(e) = app.emit(PROGRESS_EVENT, payload) {
+        eprintln!("{} {}", "[Progress emit failed]".red().bold(), e);
     }
+}
 
-    let valid_formats = vec!["jpg", "jpeg", "png", "gif", "webp", "PNG"];
-    let results = Arc::new(Mutex::new(Vec::new())); // Use Arc and Mutex to share results across threads
-    let mut handles = vec![]; // To hold thread handles
-    let start = Instant::now(); // Start timer for processing time
-
-    for entry in fs::read_dir(&options.input_folder)
+/// Reads `options.input_folder` and resolves every entry to its resize task(s) (one per preset,
+/// or a single task without presets), so the caller can size its progress counter off the real
+/// number of outputs rather than the number of input files.
+fn build_file_plans(options: &ResizeOptions) -> Result<(Vec<(PathBuf, FilePlan)>, usize), String> {
+    let valid_formats = VALID_EXTENSIONS;
+    let entries: Vec<PathBuf> = fs::read_dir(&options.input_folder)
         .map_err(|e| format!("Error reading directory: {}", e))?
-    {
-        let entry = entry.map_err(|e| format!("Error reading entry: {}", e))?;
-        let path = entry.path();
-        let ext = path
-            .extension()
-            .and_then(|e| e.to_str())
-            .unwrap_or("")
-            .to_lowercase();
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<Result<_, _>>()
+        .map_err(|e| format!("Error reading entry: {}", e))?;
 
-        let results = Arc::clone(&results); // Clone Arc to share with the thread
-        let options = options.clone(); // Clone options to pass to the thread
-        let output_folder = options.output_folder.clone(); // Clone output folder for use in the thread
+    let plans: Vec<(PathBuf, FilePlan)> = entries
+        .into_iter()
+        .map(|path| {
+            let ext = path
+                .extension()
+                .and_then(|e| e.to_str())
+                .unwrap_or("")
+                .to_lowercase();
 
-        if valid_formats.contains(&ext.as_str()) {
-            let handle = spawn(async move {
-                // Spawn an asynchronous task
-                let output_path = output_folder.join(path.file_name().unwrap());
+            if !valid_formats.contains(&ext.as_str()) {
+                return (path, FilePlan::Unsupported);
+            }
 
-                // Capture the current timestamp for processing
-                let timestamp = chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+            if let Some(filters) = &options.filters {
+                if let Err(reason) = evaluate_filters(&path, &ext, filters) {
+                    return (path, FilePlan::Filtered(reason));
+                }
+            }
 
-                let mut result = serde_json::json!( {
-                    "file": path.display().to_string(),
-                    "output_file": output_path.display().to_string(),
-                    "timestamp": timestamp,
-                    "status": "unknown",
-                    "message": ""
-                });
+            let tasks = build_tasks(&path, options);
+            (path, FilePlan::Tasks(tasks))
+        })
+        .collect();
+
+    let total: usize = plans
+        .iter()
+        .map(|(_, plan)| match plan {
+            FilePlan::Tasks(tasks) => tasks.len(),
+            _ => 1,
+        })
+        .sum();
+
+    Ok((plans, total))
+}
+
+/// Runs one resize task's manifest lookup and resize-or-skip decision, returning its result
+/// entry. Shared by `process_images` and `process_images_backgrounded`. `cancelled` lets a
+/// backgrounded job bail out before doing any work once it's been told to stop.
+fn run_resize_task(
+    path: &Path,
+    output_path: &Path,
+    preset: &Option<String>,
+    task_options: &ResizeOptions,
+    manifest: &Mutex<Manifest>,
+    cancelled: bool,
+) -> serde_json::Value {
+    let timestamp = chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+
+    let mut result = serde_json::json!( {
+        "file": path.display().to_string(),
+        "output_file": output_path.display().to_string(),
+        "preset": preset,
+        "timestamp": timestamp,
+        "status": "unknown",
+        "message": ""
+    });
 
-                if output_path.exists() && !options.overwrite {
-                    result["status"] = serde_json::json!("skipped");
-                    result["message"] = serde_json::json!("File already exists, skipping.");
-                } else {
-                    match resize_image(&path, &output_path, &options) {
-                        Ok(_) => {
-                            result["status"] = serde_json::json!("success");
-                            result["message"] = serde_json::json!("Image resized successfully.");
-                        }
-                        Err(e) => {
-                            result["status"] = serde_json::json!("error");
-                            result["message"] = serde_json::json!(e);
-                        }
-                    }
+    if cancelled {
+        result["status"] = serde_json::json!("cancelled");
+        result["message"] =
+            serde_json::json!("Job was cancelled before this file started.");
+        return result;
+    }
+
+    let manifest_key = output_path.display().to_string();
+    let task_hash = compute_task_hash(path, task_options);
+    let previous_hash = task_hash.as_ref().ok().and_then(|_| {
+        manifest
+            .lock()
+            .unwrap()
+            .get(&manifest_key)
+            .map(|entry| entry.hash.clone())
+    });
+    let unchanged = output_path.exists()
+        && matches!(
+            (&task_hash, &previous_hash),
+            (Ok(hash), Some(previous)) if hash == previous
+        );
+
+    if unchanged {
+        result["status"] = serde_json::json!("unchanged");
+        result["message"] =
+            serde_json::json!("Input and parameters unchanged since last run, skipping.");
+    // A manifest-tracked output always gets refreshed when its source hash changes, even with
+    // overwrite: false, per the original request ("re-processing when the source bytes differ
+    // even if the output filename matches"): `overwrite` only gates files this tool doesn't yet
+    // track (the branch below), not its own previously-generated outputs.
+    } else if previous_hash.is_none() && output_path.exists() && !task_options.overwrite {
+        result["status"] = serde_json::json!("skipped");
+        result["message"] = serde_json::json!("File already exists, skipping.");
+    } else {
+        match resize_image(path, output_path, task_options) {
+            Ok(report) => {
+                result["status"] = serde_json::json!("success");
+                result["message"] = serde_json::json!("Image resized successfully.");
+                result["orientation_corrected"] = serde_json::json!(report.orientation_corrected);
+                result["metadata_stripped"] = serde_json::json!(report.metadata_stripped);
+
+                if let Ok(hash) = task_hash {
+                    manifest
+                        .lock()
+                        .unwrap()
+                        .insert(manifest_key, ManifestEntry { hash });
                 }
+            }
+            Err(e) => {
+                result["status"] = serde_json::json!("error");
+                result["message"] = serde_json::json!(e);
+            }
+        }
+    }
 
-                results.lock().unwrap().push(result); // Safely push the result
-            });
+    result
+}
 
-            handles.push(handle); // Store the task handle
-        } else {
-            let timestamp = chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
-            let mut result = serde_json::json!( {
-                "file": path.display().to_string(),
-                "timestamp": timestamp,
-                "status": "unsupported_format",
-                "message": "Unsupported file format."
-            });
-            results.lock().unwrap().push(result); // Push unsupported format results
+/// Where a finished task's result lands and how progress is reported, letting `dispatch_plans`
+/// drive both the one-shot `process_images` (a plain `Vec`) and the backgrounded job queue
+/// (a `JobState`) through the same loop.
+trait ResultSink: Send + Sync {
+    fn record(&self, app: &AppHandle, result: serde_json::Value);
+    fn is_cancelled(&self) -> bool {
+        false
+    }
+}
+
+struct VecSink {
+    results: Mutex<Vec<serde_json::Value>>,
+    done: AtomicUsize,
+    total: usize,
+}
+
+impl ResultSink for VecSink {
+    fn record(&self, app: &AppHandle, result: serde_json::Value) {
+        emit_progress(app, &result, &self.done, self.total);
+        self.results.lock().unwrap().push(result);
+    }
+}
+
+struct JobSink {
+    job_id: String,
+    job: Arc<JobState>,
+}
+
+impl ResultSink for JobSink {
+    fn record(&self, app: &AppHandle, result: serde_json::Value) {
+        self.job.done.fetch_add(1, Ordering::SeqCst);
+        self.job.results.lock().unwrap().push(result.clone());
+        emit_progress_for_job(app, &self.job_id, &result, &self.job);
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.job.cancelled.load(Ordering::SeqCst)
+    }
+}
+
+/// Spawns one task per planned output (resize tasks on their own thread each; filtered/
+/// unsupported entries resolved immediately), routing every result through `sink`. Returns the
+/// resize tasks' join handles so the caller can await them before saving the manifest.
+fn dispatch_plans<S: ResultSink + 'static>(
+    app: &AppHandle,
+    plans: Vec<(PathBuf, FilePlan)>,
+    manifest: &Arc<Mutex<Manifest>>,
+    sink: &Arc<S>,
+) -> Vec<tauri::async_runtime::JoinHandle<()>> {
+    let mut handles = vec![];
+
+    for (path, plan) in plans {
+        match plan {
+            FilePlan::Tasks(tasks) => {
+                for task in tasks {
+                    let manifest = Arc::clone(manifest);
+                    let app = app.clone();
+                    let sink = Arc::clone(sink);
+                    let path = path.clone();
+                    let preset = task.preset;
+                    let task_options = task.options;
+                    let output_path = task.output_path;
+
+                    let handle = spawn(async move {
+                        let cancelled = sink.is_cancelled();
+                        let result = run_resize_task(
+                            &path,
+                            &output_path,
+                            &preset,
+                            &task_options,
+                            &manifest,
+                            cancelled,
+                        );
+                        sink.record(&app, result);
+                    });
+
+                    handles.push(handle);
+                }
+            }
+            FilePlan::Filtered(reason) => {
+                let timestamp = chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+                let result = serde_json::json!( {
+                    "file": path.display().to_string(),
+                    "timestamp": timestamp,
+                    "status": "filtered",
+                    "message": reason,
+                });
+                sink.record(app, result);
+            }
+            FilePlan::Unsupported => {
+                let timestamp = chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+                let result = serde_json::json!( {
+                    "file": path.display().to_string(),
+                    "timestamp": timestamp,
+                    "status": "unsupported_format",
+                    "message": "Unsupported file format."
+                });
+                sink.record(app, result);
+            }
         }
     }
 
+    handles
+}
+
+#[tauri::command]
+async fn process_images(app: AppHandle, options: ResizeOptions) -> Result<String, String> {
+    if !options.input_folder.exists() {
+        return Err(format!(
+            "Input folder does not exist: {:?}",
+            options.input_folder
+        ));
+    }
+
+    let (plans, total) = build_file_plans(&options)?;
+
+    let manifest = Arc::new(Mutex::new(load_manifest(&options.output_folder)));
+    let sink = Arc::new(VecSink {
+        results: Mutex::new(Vec::new()),
+        done: AtomicUsize::new(0),
+        total,
+    });
+    let start = Instant::now(); // Start timer for processing time
+
+    let handles = dispatch_plans(&app, plans, &manifest, &sink);
     for handle in handles {
         handle
             .await
             .map_err(|_| "Error joining thread".to_string())?; // Wait for all tasks to finish
     }
 
+    save_manifest(&options.output_folder, &manifest.lock().unwrap())?;
+
     let elapsed_time = start.elapsed().as_secs_f64(); // Calculate elapsed time
-    let locked_results = results.lock().unwrap(); // Lock the mutex
+    let locked_results = sink.results.lock().unwrap();
 
     // Prepare the final response including the processing summary
     let response = serde_json::json!({
@@ -172,6 +889,125 @@ async fn process_images(options: ResizeOptions) -> Result<String, String> {
     Ok(serde_json::to_string(&response).unwrap()) // Return the final response
 }
 
+#[tauri::command]
+async fn process_images_backgrounded(
+    app: AppHandle,
+    options: ResizeOptions,
+) -> Result<String, String> {
+    if !options.input_folder.exists() {
+        return Err(format!(
+            "Input folder does not exist: {:?}",
+            options.input_folder
+        ));
+    }
+
+    // Planning (reading the directory and, when filters are set, probing each file's size/
+    // dimensions) is real I/O over what can be a huge folder, so it happens inside the spawned
+    // task below rather than blocking this command's return.
+    let job_id = Uuid::new_v4().to_string();
+    let job = Arc::new(JobState {
+        cancelled: AtomicBool::new(false),
+        done: AtomicUsize::new(0),
+        total: AtomicUsize::new(0),
+        planned: AtomicBool::new(false),
+        results: Mutex::new(Vec::new()),
+    });
+    JOBS.lock()
+        .unwrap()
+        .insert(job_id.clone(), Arc::clone(&job));
+
+    let sink = Arc::new(JobSink {
+        job_id: job_id.clone(),
+        job: Arc::clone(&job),
+    });
+    let output_folder = options.output_folder.clone();
+
+    let returned_id = job_id.clone();
+    spawn(async move {
+        let (plans, total) = match build_file_plans(&options) {
+            Ok(planned) => planned,
+            Err(e) => {
+                job.results.lock().unwrap().push(serde_json::json!({
+                    "status": "error",
+                    "message": e,
+                }));
+                job.done.store(1, Ordering::SeqCst);
+                job.total.store(1, Ordering::SeqCst);
+                job.planned.store(true, Ordering::SeqCst);
+                return;
+            }
+        };
+        job.total.store(total, Ordering::SeqCst);
+        job.planned.store(true, Ordering::SeqCst);
+
+        let manifest = Arc::new(Mutex::new(load_manifest(&output_folder)));
+        let handles = dispatch_plans(&app, plans, &manifest, &sink);
+
+        for handle in handles {
+            let _ = handle.await;
+        }
+
+        if let Err(e) = save_manifest(&output_folder, &manifest.lock().unwrap()) {
+            eprintln!("{} {}", "[Manifest save failed]".red().bold(), e);
+        }
+    });
+
+    Ok(returned_id)
+}
+
+/// Emits the same `PROGRESS_EVENT` shape as `process_images`, tagged with the job id so a UI
+/// tracking several backgrounded jobs can tell them apart.
+fn emit_progress_for_job(
+    app: &AppHandle,
+    job_id: &str,
+    result: &serde_json::Value,
+    job: &JobState,
+) {
+    let payload = serde_json::json!({
+        "job_id": job_id,
+        "result": result,
+        "done": job.done.load(Ordering::SeqCst),
+        "total": job.total.load(Ordering::SeqCst),
+    });
+    if let Err(e) = app.emit(PROGRESS_EVENT, payload) {
+        eprintln!("{} {}", "[Progress emit failed]".red().bold(), e);
+    }
+}
+
+#[tauri::command]
+fn job_status(job_id: String) -> Result<String, String> {
+    let jobs = JOBS.lock().unwrap();
+    let job = jobs
+        .get(&job_id)
+        .ok_or_else(|| format!("No such job: {}", job_id))?;
+
+    let done = job.done.load(Ordering::SeqCst);
+    let total = job.total.load(Ordering::SeqCst);
+    let planned = job.planned.load(Ordering::SeqCst);
+    let results = job.results.lock().unwrap().clone();
+
+    let response = serde_json::json!({
+        "job_id": job_id,
+        "done": done,
+        "total": total,
+        "cancelled": job.cancelled.load(Ordering::SeqCst),
+        "finished": planned && done >= total,
+        "results": results,
+    });
+
+    Ok(serde_json::to_string(&response).unwrap())
+}
+
+#[tauri::command]
+fn cancel_job(job_id: String) -> Result<(), String> {
+    let jobs = JOBS.lock().unwrap();
+    let job = jobs
+        .get(&job_id)
+        .ok_or_else(|| format!("No such job: {}", job_id))?;
+    job.cancelled.store(true, Ordering::SeqCst);
+    Ok(())
+}
+
 #[tauri::command]
 fn open_file_explorer(path: &str) {
     // Execute the command to open the file explorer
@@ -181,13 +1017,253 @@ fn open_file_explorer(path: &str) {
         .expect("Failed to open file explorer");
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolve_target_dimensions_keeps_aspect_ratio_from_width() {
+        let (width, height) =
+            resolve_target_dimensions(4000, 3000, Some(800), None, true).unwrap();
+        assert_eq!((width, height), (800, 600));
+    }
+
+    #[test]
+    fn resolve_target_dimensions_keeps_aspect_ratio_from_height() {
+        let (width, height) =
+            resolve_target_dimensions(4000, 3000, None, Some(600), true).unwrap();
+        assert_eq!((width, height), (800, 600));
+    }
+
+    #[test]
+    fn resolve_target_dimensions_requires_a_dimension_when_keeping_aspect_ratio() {
+        let result = resolve_target_dimensions(4000, 3000, None, None, true);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn resolve_target_dimensions_without_aspect_ratio_falls_back_to_original() {
+        let (width, height) = resolve_target_dimensions(4000, 3000, Some(800), None, false).unwrap();
+        assert_eq!((width, height), (800, 3000));
+
+        let (width, height) = resolve_target_dimensions(4000, 3000, None, None, false).unwrap();
+        assert_eq!((width, height), (4000, 3000));
+    }
+
+    #[test]
+    fn resolve_target_dimensions_sizes_exif_corrected_rotated_source() {
+        // A sideways 4000x3000 photo (EXIF-corrected to 3000x4000 before this is called) asking
+        // for width: 800 should come out 800 wide, not 800 tall like the pre-fix orientation bug.
+        let (width, height) =
+            resolve_target_dimensions(3000, 4000, Some(800), None, true).unwrap();
+        assert_eq!((width, height), (800, 1067));
+    }
+
+    fn temp_file_with_bytes(name: &str, contents: &[u8]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("swyfts-test-{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn evaluate_filters_rejects_extension_not_in_include_list() {
+        let path = temp_file_with_bytes("photo.png", b"not a real png");
+        let filters = DimensionFilters {
+            include_extensions: Some(vec!["jpg".to_string()]),
+            ..Default::default()
+        };
+        assert!(evaluate_filters(&path, "png", &filters).is_err());
+    }
+
+    #[test]
+    fn evaluate_filters_rejects_extension_in_exclude_list() {
+        let path = temp_file_with_bytes("photo.gif", b"not a real gif");
+        let filters = DimensionFilters {
+            exclude_extensions: Some(vec!["gif".to_string()]),
+            ..Default::default()
+        };
+        assert!(evaluate_filters(&path, "gif", &filters).is_err());
+    }
+
+    #[test]
+    fn evaluate_filters_enforces_min_and_max_bytes() {
+        let path = temp_file_with_bytes("photo.jpg", &[0u8; 100]);
+
+        let too_small = DimensionFilters {
+            min_bytes: Some(200),
+            ..Default::default()
+        };
+        assert!(evaluate_filters(&path, "jpg", &too_small).is_err());
+
+        let too_large = DimensionFilters {
+            max_bytes: Some(50),
+            ..Default::default()
+        };
+        assert!(evaluate_filters(&path, "jpg", &too_large).is_err());
+
+        let within_range = DimensionFilters {
+            min_bytes: Some(50),
+            max_bytes: Some(200),
+            ..Default::default()
+        };
+        assert!(evaluate_filters(&path, "jpg", &within_range).is_ok());
+    }
+
+    #[test]
+    fn resolve_output_format_rejects_jxl_output() {
+        let result = resolve_output_format(&Some("jxl".to_string()));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn resolve_output_format_rejects_unknown_format() {
+        let result = resolve_output_format(&Some("not-a-format".to_string()));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn resolve_output_format_passes_through_known_format() {
+        let result = resolve_output_format(&Some("webp".to_string())).unwrap();
+        assert_eq!(result, Some(image::ImageFormat::WebP));
+    }
+
+    #[test]
+    fn resolve_output_format_is_none_when_unset() {
+        let result = resolve_output_format(&None).unwrap();
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn disambiguated_stem_keeps_plain_stem_without_conversion() {
+        let stem = disambiguated_stem(Path::new("photo.jpg"), &None);
+        assert_eq!(stem, "photo");
+    }
+
+    #[test]
+    fn disambiguated_stem_folds_in_original_extension_on_conversion() {
+        let jpg_stem = disambiguated_stem(Path::new("photo.jpg"), &Some("webp".to_string()));
+        let png_stem = disambiguated_stem(Path::new("photo.png"), &Some("webp".to_string()));
+        assert_eq!(jpg_stem, "photo.jpg");
+        assert_eq!(png_stem, "photo.png");
+        assert_ne!(jpg_stem, png_stem);
+    }
+
+    #[test]
+    fn build_output_path_disambiguates_same_stem_different_extensions() {
+        let output_folder = Path::new("/out");
+        let jpg_path = build_output_path(
+            output_folder,
+            Path::new("photo.jpg"),
+            &Some("webp".to_string()),
+        );
+        let png_path = build_output_path(
+            output_folder,
+            Path::new("photo.png"),
+            &Some("webp".to_string()),
+        );
+        assert_ne!(jpg_path, png_path);
+    }
+
+    #[test]
+    fn render_output_path_substitutes_all_tokens() {
+        let path = render_output_path(
+            Path::new("/out"),
+            Path::new("photo.jpg"),
+            "{stem}_{preset}_{w}x{h}.{ext}",
+            "thumb",
+            320,
+            240,
+            &None,
+        );
+        assert_eq!(path, Path::new("/out/photo_thumb_320x240.jpg"));
+    }
+
+    #[test]
+    fn render_output_path_disambiguates_same_stem_different_extensions() {
+        let template = "{stem}_{preset}.{ext}";
+        let jpg_path = render_output_path(
+            Path::new("/out"),
+            Path::new("photo.jpg"),
+            template,
+            "thumb",
+            320,
+            240,
+            &Some("webp".to_string()),
+        );
+        let png_path = render_output_path(
+            Path::new("/out"),
+            Path::new("photo.png"),
+            template,
+            "thumb",
+            320,
+            240,
+            &Some("webp".to_string()),
+        );
+        assert_ne!(jpg_path, png_path);
+    }
+
+    fn test_options(width: Option<u32>) -> ResizeOptions {
+        ResizeOptions {
+            input_folder: PathBuf::from("/in"),
+            output_folder: PathBuf::from("/out"),
+            width,
+            height: None,
+            keep_aspect_ratio: true,
+            overwrite: false,
+            output_format: None,
+            strip_metadata: true,
+            filters: None,
+            presets: Vec::new(),
+            output_template: None,
+        }
+    }
+
+    #[test]
+    fn compute_task_hash_is_stable_for_same_input_and_params() {
+        let path = temp_file_with_bytes("source.jpg", b"same bytes every time");
+        let options = test_options(Some(800));
+
+        let first = compute_task_hash(&path, &options).unwrap();
+        let second = compute_task_hash(&path, &options).unwrap();
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn compute_task_hash_changes_when_source_bytes_change() {
+        let path = temp_file_with_bytes("source.jpg", b"original bytes");
+        let options = test_options(Some(800));
+        let before = compute_task_hash(&path, &options).unwrap();
+
+        fs::write(&path, b"edited bytes").unwrap();
+        let after = compute_task_hash(&path, &options).unwrap();
+
+        assert_ne!(before, after);
+    }
+
+    #[test]
+    fn compute_task_hash_changes_when_params_change() {
+        let path = temp_file_with_bytes("source.jpg", b"same bytes every time");
+        let at_800 = compute_task_hash(&path, &test_options(Some(800))).unwrap();
+        let at_1600 = compute_task_hash(&path, &test_options(Some(1600))).unwrap();
+        assert_ne!(at_800, at_1600);
+    }
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_process::init())
         .plugin(tauri_plugin_updater::Builder::new().build())
         .plugin(tauri_plugin_dialog::init())
-        .invoke_handler(tauri::generate_handler![process_images, open_file_explorer])
+        .invoke_handler(tauri::generate_handler![
+            process_images,
+            process_images_backgrounded,
+            job_status,
+            cancel_job,
+            open_file_explorer
+        ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }